@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::io::{Write, BufWriter};
 
+use futures::Future;
 use inflector::Inflector;
 
 use Service;
@@ -29,17 +30,155 @@ mod type_filter;
 type FileWriter = BufWriter<File>;
 type IoResult = ::std::io::Result<()>;
 
+/// The future returned by every generated client method. Boxing it lets each protocol
+/// generator emit the same return type regardless of how its response is parsed, so
+/// callers can drive many requests concurrently on a single thread instead of blocking
+/// on `dispatcher.dispatch()`.
+pub type RusotoFuture<T, E> = Box<Future<Item = T, Error = E> + Send>;
+
+/// Rust source for an operation's client-trait method signature, e.g.
+/// `fn get_object(&self, input: GetObjectRequest) -> RusotoFuture<GetObjectOutput, GetObjectError>;`.
+/// `generate_method_signatures` implementations should build each signature out of this rather
+/// than hand-rolling it, so every protocol's trait method actually returns the boxed future
+/// instead of a blocking `Result`.
+pub fn generate_future_method_signature(
+    operation_name: &str,
+    input_type: Option<&str>,
+    output_type: &str,
+    error_type: &str,
+) -> String {
+    format!(
+        "    fn {method_name}(&self{input_param}) -> RusotoFuture<{output_type}, {error_type}>;",
+        method_name = operation_name.to_snake_case(),
+        input_param = input_type.map(|t| format!(", input: {}", t)).unwrap_or_default(),
+        output_type = output_type,
+        error_type = error_type,
+    )
+}
+
+/// Rust source for an operation's client-trait method body: run `build_request_expr`
+/// (caller-supplied Rust statements, spliced in as-is, which must themselves bind a local
+/// `request`), resolve credentials and sign it, run `post_sign_expr` (for anything that, like the chunk
+/// signer, needs state `request.sign()` itself produces — `signing_key`/`scope`/`amz_date`/
+/// `header_signature` don't exist beforehand), dispatch the now-signed request, and thread the
+/// result through `parse_response_expr` to produce the operation's `RusotoFuture<Output, Error>`.
+/// This owns only the future-boxing/signing boilerplate that's identical across protocols;
+/// `build_request_expr`/`post_sign_expr`/`parse_response_expr` let each protocol plug in its own
+/// request serialization and response parsing rather than blocking on `dispatcher.dispatch()`.
+pub fn generate_future_method_impl(
+    operation_name: &str,
+    input_type: Option<&str>,
+    output_type: &str,
+    error_type: &str,
+    build_request_expr: &str,
+    post_sign_expr: &str,
+    parse_response_expr: &str,
+) -> String {
+    format!(
+        "    fn {method_name}(&self{input_param}) -> RusotoFuture<{output_type}, {error_type}> {{
+        {build_request_expr}
+
+        Box::new(self.credentials_provider.credentials()
+            .map_err({error_type}::from)
+            .and_then(move |credentials| {{
+                request.sign(&credentials);
+{post_sign_expr}
+                self.dispatcher.dispatch(request).map_err({error_type}::from)
+            }})
+            .and_then(move |response| {parse_response_expr}))
+    }}
+",
+        method_name = operation_name.to_snake_case(),
+        input_param = input_type.map(|t| format!(", input: {}", t)).unwrap_or_default(),
+        output_type = output_type,
+        error_type = error_type,
+        build_request_expr = build_request_expr,
+        post_sign_expr = post_sign_expr,
+        parse_response_expr = parse_response_expr,
+    )
+}
+
 /// Abstracts the generation of Rust code for various AWS protocols
 pub trait GenerateProtocol {
     /// Generate the various `use` statements required by the module generatedfor this service
     fn generate_prelude(&self, writer: &mut FileWriter, service: &Service) -> IoResult;
 
-    fn generate_method_signatures(&self, writer: &mut FileWriter, service: &Service) -> IoResult;
+    /// Generate a signature for each `Operation` in the `Service`. The default builds every
+    /// signature out of [`generate_future_method_signature`] so it returns
+    /// `RusotoFuture<Output, FooError>` rather than a blocking `Result`; override only if a
+    /// protocol needs a different signature shape.
+    fn generate_method_signatures(&self, writer: &mut FileWriter, service: &Service) -> IoResult {
+        for (operation_name, operation) in service.operations().iter() {
+            let input_type = operation.input.as_ref().map(|i| mutate_type_name(&i.shape));
+            let output_type = operation.output.as_ref()
+                .map(|o| mutate_type_name(&o.shape))
+                .unwrap_or_else(|| "()".to_owned());
+            let error_type = error_type_name(operation_name);
+
+            writeln!(writer, "{}", generate_future_method_signature(
+                operation_name,
+                input_type.as_ref().map(String::as_str),
+                &output_type,
+                &error_type,
+            ))?;
+        }
+        Ok(())
+    }
 
     /// Generate a method for each `Operation` in the `Service` to execute that method remotely
     ///
-    /// The method generated by this method are inserted into an enclosing `impl FooClient {}` block
-    fn generate_method_impls(&self, writer: &mut FileWriter, service: &Service) -> IoResult;
+    /// The methods generated by this method are inserted into an enclosing `impl FooClient {}`
+    /// block. The default builds each method out of [`generate_future_method_impl`], so the
+    /// returned `RusotoFuture` signs the request and chains the dispatcher's own future rather
+    /// than blocking on `dispatcher.dispatch()`. The default does not parse the response: its
+    /// response step is `unimplemented!(...)`, so the generated method panics once a response
+    /// arrives. For a streaming operation of a rest-json or rest-xml service it also marks the request `aws-chunked` before signing (via
+    /// [`rest_request_generator::generate_chunked_request_headers`]) and, once signing has
+    /// produced a header signature to seed from, constructs the chunk signer and wraps the
+    /// payload (via [`rest_request_generator::generate_chunk_payload_signer`], run as
+    /// `post_sign_expr` so it never reads `request.signing_key()`/`header_signature()` before
+    /// they exist).
+    fn generate_method_impls(&self, writer: &mut FileWriter, service: &Service) -> IoResult {
+        for (operation_name, operation) in service.operations().iter() {
+            let input_type = operation.input.as_ref().map(|i| mutate_type_name(&i.shape));
+            let output_type = operation.output.as_ref()
+                .map(|o| mutate_type_name(&o.shape))
+                .unwrap_or_else(|| "()".to_owned());
+            let error_type = error_type_name(operation_name);
+
+            let mut build_request_expr = format!(
+                "let mut request = ::rusoto_core::signature::SignedRequest::new({method:?}, {signing_name:?}, &self.region, {uri:?});",
+                method = operation.http.method,
+                signing_name = service.signing_name(),
+                uri = operation.http.request_uri,
+            );
+
+            let mut post_sign_expr = String::new();
+
+            if let Some(ref input) = operation.input {
+                let input_shape = service.get_shape(&input.shape).unwrap();
+                let chunked_protocol = service.protocol() == "rest-json" || service.protocol() == "rest-xml";
+                if chunked_protocol && shape_has_any_streaming_member(input_shape) {
+                    build_request_expr.push('\n');
+                    build_request_expr.push_str(&rest_request_generator::generate_chunked_request_headers());
+                    post_sign_expr.push_str(&rest_request_generator::generate_chunk_payload_signer(
+                        input_type.as_ref().unwrap(),
+                    ));
+                }
+            }
+
+            writeln!(writer, "{}", generate_future_method_impl(
+                operation_name,
+                input_type.as_ref().map(String::as_str),
+                &output_type,
+                &error_type,
+                &build_request_expr,
+                &post_sign_expr,
+                "unimplemented!(\"response parsing is protocol-specific\")",
+            ))?;
+        }
+        Ok(())
+    }
 
     /// Add any attributes that should decorate the struct for the given type (typically `Debug`, `Clone`, etc.)
     fn generate_struct_attributes(&self, serialized: bool, deserialized: bool) -> String;
@@ -53,17 +192,107 @@ pub trait GenerateProtocol {
         None
     }
 
-    /// If necessary, generate a deserializer for the specified type
+    /// If necessary, generate a deserializer for the specified type. The default handles the
+    /// common case of a response shape whose *only* member is a streaming blob (e.g.
+    /// `GetObjectOutput`-style bodies with no other fields to parse off headers), building it
+    /// straight from the dispatched response via `rest_response_parser`/`xml_payload_parser`
+    /// rather than the usual buffered-body deserializer. Shapes with non-streaming members
+    /// alongside the streaming one need full protocol-specific header/body parsing and return
+    /// `None` here, same as the default for any non-streaming shape.
     fn generate_deserializer(&self,
-                             _name: &str,
-                             _shape: &Shape,
-                             _service: &Service)
+                             name: &str,
+                             shape: &Shape,
+                             service: &Service)
                              -> Option<String> {
-        None
+        let members = shape.members.as_ref()?;
+        if members.len() != 1 {
+            return None;
+        }
+        let (member_name, member) = members.iter().next().unwrap();
+        if !member.streaming() {
+            return None;
+        }
+
+        let field_name = generate_field_name(member_name);
+        let streaming_type = mutate_type_name_for_streaming(&member.shape);
+        let body_expr = if service.protocol() == "rest-xml" {
+            xml_payload_parser::generate_streaming_member_parser(&streaming_type)
+        } else {
+            rest_response_parser::generate_streaming_member_parser(&streaming_type)
+        };
+
+        Some(format!(
+            "impl {name} {{
+    /// Builds a `{name}` directly from the dispatched response rather than the usual
+    /// buffered-body deserializer, since its only member, `{field}`, is a streaming blob.
+    fn from_streaming_response(response: ::rusoto_core::request::HttpResponse) -> {name} {{
+        {name} {{
+            {field}: {body_expr},
+        }}
+    }}
+}}",
+            name = name,
+            field = field_name,
+            body_expr = body_expr,
+        ))
     }
 
     /// Return the type used by this protocol for timestamps
     fn timestamp_type(&self) -> &'static str;
+
+    /// Emit a SigV4 `aws-chunked` chunk signer for every operation of a rest-json or rest-xml
+    /// service whose input carries a streaming blob member, so uploads can be framed and signed chunk-by-chunk. This only
+    /// defines the `{Input}ChunkSigner` type; `generate_method_impls` is what actually
+    /// constructs and uses one for a streaming operation, via
+    /// `rest_request_generator::generate_chunked_request_headers`/`generate_chunk_payload_signer`.
+    fn generate_chunked_body_encoder(&self, writer: &mut FileWriter, service: &Service) -> IoResult {
+        if service.protocol() != "rest-json" && service.protocol() != "rest-xml" {
+            return Ok(());
+        }
+
+        for (operation_name, operation) in service.operations().iter() {
+            let input = match operation.input {
+                Some(ref input) => input,
+                None => continue,
+            };
+            let input_shape = service.get_shape(&input.shape).unwrap();
+            if !shape_has_any_streaming_member(input_shape) {
+                continue;
+            }
+
+            let input_type = mutate_type_name(&input.shape);
+            let generated = rest_request_generator::generate_chunk_signer(operation_name, &input_type);
+            writeln!(writer, "{}", generated)?;
+        }
+        Ok(())
+    }
+
+    /// Emit a `get_presigned_{operation}` method, built from the operation's HTTP method and
+    /// request URI binding, for every operation of a rest-json or rest-xml service that has an
+    /// input shape, returning a SigV4 query-signed URL that can be shared without a network round
+    /// trip. Operations without an input shape, and services of other protocols, get none.
+    fn generate_presigned_methods(&self, writer: &mut FileWriter, service: &Service) -> IoResult {
+        if service.protocol() != "rest-json" && service.protocol() != "rest-xml" {
+            return Ok(());
+        }
+
+        for (operation_name, operation) in service.operations().iter() {
+            let input_type = match operation.input {
+                Some(ref input) => mutate_type_name(&input.shape),
+                None => continue,
+            };
+
+            let generated = rest_request_generator::generate_presigned_method(
+                service.signing_name(),
+                operation_name,
+                &operation.http.method,
+                &operation.http.request_uri,
+                &input_type,
+            );
+            writeln!(writer, "{}", generated)?;
+        }
+        Ok(())
+    }
 }
 
 pub fn generate_source(service: &Service, writer: &mut FileWriter) -> IoResult {
@@ -115,15 +344,15 @@ fn generate<P, E>(writer: &mut FileWriter,
         // =================================================================
 
         #[allow(warnings)]
-        use hyper::Client;
         use hyper::status::StatusCode;
+        use bytes::Bytes;
+        use futures::{{Future, Poll, Stream}};
         use rusoto_core::request::DispatchSignedRequest;
         use rusoto_core::region;
 
         use std::fmt;
         use std::error::Error;
         use std::io;
-        use std::io::Read;
         use rusoto_core::request::HttpDispatchError;
         use rusoto_core::credential::{{CredentialsError, ProvideAwsCredentials}};
     ")?;
@@ -132,6 +361,7 @@ fn generate<P, E>(writer: &mut FileWriter,
     generate_types(writer, service, &protocol_generator)?;
     error_type_generator
         .generate_error_types(writer, service)?;
+    protocol_generator.generate_chunked_body_encoder(writer, service)?;
     generate_client(writer, service, &protocol_generator)?;
     generate_tests(writer, service)?;
 
@@ -185,6 +415,14 @@ fn generate_client<P>(writer: &mut FileWriter,
     )?;
     protocol_generator
         .generate_method_impls(writer, service)?;
+    writeln!(writer, "}}")?;
+
+    writeln!(writer,
+        "impl<P, D> {type_name}<P, D> where P: ProvideAwsCredentials, D: DispatchSignedRequest {{",
+        type_name = service.client_type_name(),
+    )?;
+    protocol_generator
+        .generate_presigned_methods(writer, service)?;
     writeln!(writer, "}}")
 }
 
@@ -211,7 +449,13 @@ pub fn get_rust_type<'a>(
             ShapeType::Double => ("f64".into(), Ownership::Owned),
             ShapeType::Float => ("f32".into(), Ownership::Owned),
             ShapeType::Integer | ShapeType::Long => ("i64".into(), Ownership::Owned),
-            ShapeType::String => ("&'a str".into(), Ownership::Borrowed),
+            ShapeType::String => {
+                if shape.enum_values.is_some() {
+                    (mutate_type_name(shape_name), Ownership::Owned)
+                } else {
+                    ("&'a str".into(), Ownership::Borrowed)
+                }
+            }
             ShapeType::Timestamp => (for_timestamps.into(), Ownership::Owned),
             ShapeType::List => {
                 let (list_type, _) = get_rust_type(
@@ -271,7 +515,13 @@ fn get_shape_ownership(service: &Service, shape: &Shape) -> Ownership {
 fn get_member_ownership(service: &Service, member: &Member) -> Ownership {
   if let Some(member_shape) = service.get_shape(&member.shape) {
     match member_shape.shape_type {
-      ShapeType::String => return Ownership::Borrowed,
+      // Mirrors get_rust_type: an enum-constrained string shape generates an owned Rust
+      // enum, not a borrowed `&'a str`, so it must not force the embedding struct Borrowed.
+      ShapeType::String => {
+        if member_shape.enum_values.is_none() {
+          return Ownership::Borrowed;
+        }
+      }
       ShapeType::Map => {
         let key_ownership = get_shape_ownership(service, service.get_shape(member_shape.key_type()).unwrap());
         let value_ownership = get_shape_ownership(service, service.get_shape(member_shape.value_type()).unwrap());
@@ -320,6 +570,14 @@ fn is_input_shape(service: &Service, name: &str) -> bool {
            .any(|(_, op)| op.input.is_some() && op.input.as_ref().unwrap().shape == name)
 }
 
+fn shape_has_any_streaming_member(shape: &Shape) -> bool {
+    shape.members.is_some() &&
+    shape.members.as_ref()
+                 .unwrap()
+                 .iter()
+                 .any(|(_, member)| member.streaming())
+}
+
 // do any type name mutation needed to avoid collisions with Rust types
 fn mutate_type_name(type_name: &str) -> String {
     let capitalized = util::capitalize_first(type_name.to_owned());
@@ -347,6 +605,14 @@ pub fn mutate_type_name_for_streaming(type_name: &str) -> String {
     format!("Streaming{}", type_name)
 }
 
+/// The Rust source for constructing a `Streaming{name}` value from a response body.
+/// `{streaming_name}` is now backed by a `futures::Stream`, not a `Box<Read>`, so
+/// `xml_payload_parser` and `rest_response_parser` must box the body stream directly here
+/// rather than wrapping a blocking reader.
+pub fn streaming_body_constructor(streaming_name: &str, body_stream_expr: &str) -> String {
+    format!("{}(Box::new({}))", streaming_name, body_stream_expr)
+}
+
 fn generate_types<P>(writer: &mut FileWriter, service: &Service, protocol_generator: &P) -> IoResult
     where P: GenerateProtocol
 {
@@ -382,13 +648,39 @@ fn generate_types<P>(writer: &mut FileWriter, service: &Service, protocol_genera
                   deserialized,
                   protocol_generator);
                 writeln!(writer, "{}", generated)?;
+
+                // Operation inputs also get a companion builder, so callers have a
+                // discoverable, chainable way to assemble a request instead of writing out
+                // every `None` field by hand or relying on `..Default::default()`.
+                if is_input_shape(service, name) && shape.members.as_ref().map_or(false, |m| !m.is_empty()) {
+                    let generated_builder = generate_builder(
+                      &mut Memo::new(),
+                      service,
+                      &type_name,
+                      shape,
+                      protocol_generator);
+                    writeln!(writer, "{}", generated_builder)?;
+                }
+            }
+        }
+
+        // Shapes of type `string` that also carry an `enum` constraint get a real Rust enum
+        // instead of the usual `&'a str`, so invalid values are caught at compile time.
+        if shape.shape_type == ShapeType::String {
+            if let Some(ref enum_values) = shape.enum_values {
+                if let Some(ref docs) = shape.documentation {
+                    writeln!(writer, "/// {}", docs)?;
+                }
+
+                let generated = generate_enum(&type_name, enum_values);
+                writeln!(writer, "{}", generated)?;
             }
         }
 
         if is_streaming_shape(service, name) {
             // Add a second type for streaming blobs, which are the only streaming type we can have
             writeln!(writer,
-                     "pub struct {streaming_name}(Box<Read>);
+                     "pub struct {streaming_name}(Box<Stream<Item = Bytes, Error = io::Error> + Send>);
 
                      impl fmt::Debug for {streaming_name} {{
                          fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {{
@@ -396,23 +688,12 @@ fn generate_types<P>(writer: &mut FileWriter, service: &Service, protocol_genera
                          }}
                      }}
 
-                     impl ::std::ops::Deref for {streaming_name} {{
-                         type Target = Box<Read>;
+                     impl Stream for {streaming_name} {{
+                         type Item = Bytes;
+                         type Error = io::Error;
 
-                         fn deref(&self) -> &Box<Read> {{
-                             &self.0
-                         }}
-                     }}
-
-                     impl ::std::ops::DerefMut for {streaming_name} {{
-                         fn deref_mut(&mut self) -> &mut Box<Read> {{
-                             &mut self.0
-                         }}
-                     }}
-
-                     impl ::std::io::Read for {streaming_name} {{
-                         fn read(&mut self, buf: &mut [u8]) -> ::std::io::Result<usize> {{
-                             self.0.read(buf)
+                         fn poll(&mut self) -> Poll<Option<Bytes>, io::Error> {{
+                             self.0.poll()
                          }}
                      }}",
                      name = type_name,
@@ -497,6 +778,8 @@ fn generate_struct_fields<'a, P: GenerateProtocol>(
             lines.push(format!("/// {}", docs));
         }
 
+        let member_shape = service.shape_for_member(member).unwrap();
+
         if serde_attrs {
             lines.push(format!("#[serde(rename=\"{}\")]", member_name));
 
@@ -513,7 +796,9 @@ fn generate_struct_fields<'a, P: GenerateProtocol>(
                     lines.push("#[serde(skip_serializing_if=\"Option::is_none\")]".to_owned());
                 }
 
-                if shape_type == ShapeType::String {
+                // Generated enum types are owned (they have no lifetime parameter), so
+                // `#[serde(borrow)]` only makes sense for the plain `&'a str` case.
+                if shape_type == ShapeType::String && member_shape.enum_values.is_none() {
                   lines.push(
                     "#[serde(borrow)]".to_owned()
                   );
@@ -521,7 +806,6 @@ fn generate_struct_fields<'a, P: GenerateProtocol>(
             }
         }
 
-        let member_shape = service.shape_for_member(member).unwrap();
         let (rs_type, rs_ownership) = get_rust_type(
                                     memo,
                                     service,
@@ -547,7 +831,338 @@ fn generate_struct_fields<'a, P: GenerateProtocol>(
     (fields, ownership)
 }
 
+/// Generate a `{name}Builder` companion type for an operation input shape: a
+/// `Default`-derived struct holding every member as `Option<T>`, with a fluent `fn
+/// field(mut self, value: T) -> Self` setter per member (taking the field's own type by
+/// value, not `Option<T>`), and a `fn build(self) -> {name}` that assembles the finished
+/// input, panicking with a descriptive message if a required field was never set.
+fn generate_builder<'a, P: GenerateProtocol>(
+  memo: &mut Memo<'a>,
+  service: &Service,
+  name: &'a str,
+  shape: &Shape,
+  protocol_generator: &P
+) -> String {
+    let members: Vec<(&String, &Member)> = shape.members.as_ref().unwrap().iter()
+        .filter(|&(_, member)| member.deprecated != Some(true))
+        .collect();
+
+    let mut ownership = Ownership::Owned;
+    let mut builder_fields = Vec::new();
+    let mut setters = Vec::new();
+    let mut build_fields = Vec::new();
+
+    for &(member_name, member) in &members {
+        let field_name = generate_field_name(member_name);
+        let member_shape = service.shape_for_member(member).unwrap();
+        let (rs_type, rs_ownership) = get_rust_type(
+          memo,
+          service,
+          &member.shape,
+          member_shape,
+          member.streaming() && !is_input_shape(service, name),
+          protocol_generator.timestamp_type());
+        if rs_ownership == Ownership::Borrowed {
+            ownership = Ownership::Borrowed;
+        }
+
+        builder_fields.push(format!("pub {}: Option<{}>,", field_name, rs_type));
+        setters.push(builder_setter(&field_name, &rs_type));
+        build_fields.push(builder_build_field(&field_name, shape.required(member_name)));
+    }
+
+    let lifetime = if ownership == Ownership::Owned { "" } else { "<'a>" };
+
+    format!(
+        "#[derive(Default)]
+pub struct {name}Builder{lifetime} {{
+    {builder_fields}
+}}
+
+impl{lifetime} {name}Builder{lifetime} {{
+{setters}
+
+{build_doc_comment}
+    pub fn build(self) -> {name}{lifetime} {{
+        {name} {{
+            {build_fields}
+        }}
+    }}
+}}
+",
+        name = name,
+        lifetime = lifetime,
+        builder_fields = builder_fields.join("\n    "),
+        setters = setters.join("\n\n"),
+        build_doc_comment = builder_build_doc_comment(name),
+        build_fields = build_fields.join(",\n            "),
+    )
+}
+
+/// Doc comment for the generated `{name}Builder::build()` method, calling out loudly that it
+/// panics rather than offering any compile-time guarantee: there is no typestate stopping
+/// `build()` from running before every required setter has, so a forgotten or typo'd setter call
+/// is only caught at runtime, at the `build()` call site rather than the one that omitted it.
+fn builder_build_doc_comment(name: &str) -> String {
+    format!(
+        "    /// Assembles the finished `{name}`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a required field was never set. This is a runtime check, not a compile-time
+    /// guarantee — there is no typestate preventing `build()` from being called before every
+    /// required setter has run, so a forgotten or typo'd setter call surfaces here rather than
+    /// at the call site that omitted it.",
+        name = name,
+    )
+}
+
+/// Rust source for a `{Name}Builder` fluent setter taking `value: {ty}` by value and stashing
+/// it as `Some(value)`.
+fn builder_setter(field_name: &str, rs_type: &str) -> String {
+    format!(
+        "    pub fn {field}(mut self, value: {ty}) -> Self {{
+        self.{field} = Some(value);
+        self
+    }}",
+        field = field_name,
+        ty = rs_type,
+    )
+}
+
+/// Rust source for one field of a builder's `build()` struct literal: `.expect()`s a required
+/// field out of its `Option`, or passes an optional field straight through.
+fn builder_build_field(field_name: &str, required: bool) -> String {
+    if required {
+        format!(
+            "{field}: self.{field}.expect(\"missing required field `{field}`\")",
+            field = field_name,
+        )
+    } else {
+        format!("{field}: self.{field}", field = field_name)
+    }
+}
+
+// Normalize a botocore enum value (e.g. `STANDARD_IA`, `bucket-owner-full-control`) into a
+// strict PascalCase variant name.
+fn enum_variant_name(value: &str) -> String {
+    value.replace("-", "_").to_pascal_case()
+}
+
+/// Generate a Rust enum for a botocore string shape that carries an `enum` constraint, along
+/// with `FromStr`, `Display`, `AsRef<str>` and hand-rolled `Serialize`/`Deserialize` impls that
+/// round-trip the wire strings. An `Unknown(String)` variant preserves forward compatibility
+/// with values AWS adds after this code was generated.
+fn generate_enum(name: &str, values: &[String]) -> String {
+    let variants: Vec<String> = values.iter().map(|value| enum_variant_name(value)).collect();
+
+    let mut seen: HashMap<&str, &str> = HashMap::new();
+    for (value, variant) in values.iter().zip(variants.iter()) {
+        if let Some(other_value) = seen.insert(variant.as_str(), value.as_str()) {
+            panic!(
+                "enum shape {} has values {:?} and {:?} that both normalize to the variant `{}`",
+                name, other_value, value, variant
+            );
+        }
+    }
+
+    let variant_defs = variants.iter()
+        .map(|variant| format!("    {},", variant))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let from_str_arms = values.iter().zip(variants.iter())
+        .map(|(value, variant)| format!("            {:?} => {}::{},", value, name, variant))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let as_str_arms = variants.iter().zip(values.iter())
+        .map(|(variant, value)| format!("            {}::{} => {:?},", name, variant, value))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    format!(
+        "#[derive(Debug, PartialEq, Clone)]
+pub enum {name} {{
+{variant_defs}
+    Unknown(String),
+}}
+
+impl ::std::str::FromStr for {name} {{
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {{
+        Ok(match s {{
+{from_str_arms}
+            other => {name}::Unknown(other.to_owned()),
+        }})
+    }}
+}}
+
+impl ::std::fmt::Display for {name} {{
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {{
+        write!(f, \"{{}}\", self.as_ref())
+    }}
+}}
+
+impl AsRef<str> for {name} {{
+    fn as_ref(&self) -> &str {{
+        match *self {{
+{as_str_arms}
+            {name}::Unknown(ref s) => s,
+        }}
+    }}
+}}
+
+impl ::serde::Serialize for {name} {{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: ::serde::Serializer {{
+        serializer.serialize_str(self.as_ref())
+    }}
+}}
+
+impl<'de> ::serde::Deserialize<'de> for {name} {{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: ::serde::Deserializer<'de> {{
+        let s = String::deserialize(deserializer)?;
+        Ok(s.parse().unwrap())
+    }}
+}}
+",
+        name = name,
+        variant_defs = variant_defs,
+        from_str_arms = from_str_arms,
+        as_str_arms = as_str_arms,
+    )
+}
+
 fn error_type_name(name: &str) -> String {
     let type_name = mutate_type_name(name);
     format!("{}Error", type_name)
 }
+
+#[cfg(test)]
+mod builder_codegen_tests {
+    use super::{builder_build_doc_comment, builder_build_field, builder_setter};
+
+    #[test]
+    fn builder_build_doc_comment_warns_that_build_panics_at_runtime() {
+        let doc = builder_build_doc_comment("PutObjectRequest");
+        assert!(doc.contains("Assembles the finished `PutObjectRequest`"));
+        assert!(doc.contains("# Panics"));
+        assert!(doc.contains("not a compile-time"));
+    }
+
+    #[test]
+    fn builder_setter_wraps_the_value_in_some() {
+        let setter = builder_setter("bucket", "String");
+        assert!(setter.contains("pub fn bucket(mut self, value: String) -> Self"));
+        assert!(setter.contains("self.bucket = Some(value);"));
+    }
+
+    #[test]
+    fn builder_build_field_expects_required_fields() {
+        assert_eq!(
+            builder_build_field("bucket", true),
+            "bucket: self.bucket.expect(\"missing required field `bucket`\")",
+        );
+    }
+
+    #[test]
+    fn builder_build_field_passes_optional_fields_through() {
+        assert_eq!(builder_build_field("prefix", false), "prefix: self.prefix");
+    }
+}
+
+#[cfg(test)]
+mod future_method_codegen_tests {
+    use super::{generate_future_method_impl, generate_future_method_signature};
+
+    #[test]
+    fn signature_returns_a_boxed_future_not_a_blocking_result() {
+        let signature = generate_future_method_signature(
+            "GetObject",
+            Some("GetObjectRequest"),
+            "GetObjectOutput",
+            "GetObjectError",
+        );
+        assert_eq!(
+            signature,
+            "    fn get_object(&self, input: GetObjectRequest) -> RusotoFuture<GetObjectOutput, GetObjectError>;",
+        );
+    }
+
+    #[test]
+    fn signature_omits_the_input_param_when_the_operation_takes_none() {
+        let signature = generate_future_method_signature("ListBuckets", None, "ListBucketsOutput", "S3Error");
+        assert_eq!(
+            signature,
+            "    fn list_buckets(&self) -> RusotoFuture<ListBucketsOutput, S3Error>;",
+        );
+    }
+
+    #[test]
+    fn impl_chains_the_dispatcher_future_instead_of_blocking() {
+        let generated = generate_future_method_impl(
+            "GetObject",
+            Some("GetObjectRequest"),
+            "GetObjectOutput",
+            "GetObjectError",
+            "let request = build_get_object_request(&input);",
+            "",
+            "parse_get_object_response(response)",
+        );
+
+        assert!(generated.contains("fn get_object(&self, input: GetObjectRequest) -> RusotoFuture<GetObjectOutput, GetObjectError> {"));
+        assert!(generated.contains("let request = build_get_object_request(&input);"));
+        assert!(generated.contains("Box::new(self.credentials_provider.credentials()"));
+        assert!(generated.contains("self.dispatcher.dispatch(request).map_err(GetObjectError::from)"));
+        assert!(generated.contains(".and_then(move |response| parse_get_object_response(response)))"));
+    }
+
+    #[test]
+    fn impl_signs_the_request_before_running_post_sign_expr() {
+        let generated = generate_future_method_impl(
+            "PutObject",
+            Some("PutObjectRequest"),
+            "PutObjectOutput",
+            "PutObjectError",
+            "let request = build_put_object_request(&input);",
+            "                seed_chunk_signer(&request);",
+            "parse_put_object_response(response)",
+        );
+
+        let sign_pos = generated.find("request.sign(&credentials);").unwrap();
+        let post_sign_pos = generated.find("seed_chunk_signer(&request);").unwrap();
+        let dispatch_pos = generated.find("self.dispatcher.dispatch(request)").unwrap();
+        assert!(sign_pos < post_sign_pos, "request must be signed before post_sign_expr runs");
+        assert!(post_sign_pos < dispatch_pos, "post_sign_expr must run before the request is dispatched");
+    }
+}
+
+#[cfg(test)]
+mod enum_codegen_tests {
+    use super::{enum_variant_name, generate_enum};
+
+    #[test]
+    fn enum_variant_name_pascal_cases_and_strips_hyphens() {
+        assert_eq!(enum_variant_name("STANDARD_IA"), "StandardIa");
+        assert_eq!(enum_variant_name("bucket-owner-full-control"), "BucketOwnerFullControl");
+    }
+
+    #[test]
+    fn generate_enum_emits_a_variant_and_unknown_fallback() {
+        let values = vec!["STANDARD".to_owned(), "REDUCED_REDUNDANCY".to_owned()];
+        let generated = generate_enum("StorageClass", &values);
+
+        assert!(generated.contains("pub enum StorageClass {"));
+        assert!(generated.contains("Standard,"));
+        assert!(generated.contains("ReducedRedundancy,"));
+        assert!(generated.contains("Unknown(String),"));
+    }
+
+    #[test]
+    #[should_panic(expected = "both normalize to the variant `Standard`")]
+    fn generate_enum_panics_on_colliding_variant_names() {
+        let values = vec!["STANDARD".to_owned(), "Standard".to_owned()];
+        generate_enum("StorageClass", &values);
+    }
+}
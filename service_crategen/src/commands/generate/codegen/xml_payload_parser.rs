@@ -0,0 +1,8 @@
+use super::streaming_body_constructor;
+
+/// Generate the expression that builds a streaming member's value out of the rest-xml
+/// response body, now that the body is consumed as a `futures::Stream` of chunks instead of
+/// being buffered behind a `Box<Read>`.
+pub fn generate_streaming_member_parser(streaming_name: &str) -> String {
+    streaming_body_constructor(streaming_name, "response.body")
+}